@@ -0,0 +1,174 @@
+use crate::error::DecodeError;
+use crate::filter::unfilter;
+
+struct Pass {
+    x_origin: u32,
+    y_origin: u32,
+    x_stride: u32,
+    y_stride: u32,
+}
+
+// https://www.w3.org/TR/2003/REC-PNG-20031110/#8InterlaceMethods
+const PASSES: [Pass; 7] = [
+    Pass {
+        x_origin: 0,
+        y_origin: 0,
+        x_stride: 8,
+        y_stride: 8,
+    },
+    Pass {
+        x_origin: 4,
+        y_origin: 0,
+        x_stride: 8,
+        y_stride: 8,
+    },
+    Pass {
+        x_origin: 0,
+        y_origin: 4,
+        x_stride: 4,
+        y_stride: 8,
+    },
+    Pass {
+        x_origin: 2,
+        y_origin: 0,
+        x_stride: 4,
+        y_stride: 4,
+    },
+    Pass {
+        x_origin: 0,
+        y_origin: 2,
+        x_stride: 2,
+        y_stride: 4,
+    },
+    Pass {
+        x_origin: 1,
+        y_origin: 0,
+        x_stride: 2,
+        y_stride: 2,
+    },
+    Pass {
+        x_origin: 0,
+        y_origin: 1,
+        x_stride: 1,
+        y_stride: 2,
+    },
+];
+
+fn pass_dimension(full: u32, origin: u32, stride: u32) -> u32 {
+    if full <= origin {
+        0
+    } else {
+        (full - origin).div_ceil(stride)
+    }
+}
+
+/// Reassembles an Adam7-interlaced image into a single full-resolution,
+/// row-major buffer laid out the same way a non-interlaced image would be:
+/// `line_size` bytes of unfiltered pixel data per row. Each of the seven
+/// passes is unfiltered independently (it has its own scanlines and filter
+/// bytes), then scattered into the full-resolution buffer at
+/// `(x_origin + col * x_stride, y_origin + row * y_stride)`. Passes with
+/// zero width or height (small images leave some passes empty) are skipped.
+pub fn deinterlace(
+    raw: &[u8],
+    width: u32,
+    height: u32,
+    bits_per_pixel: usize,
+    bpp: usize,
+) -> Result<Vec<u8>, DecodeError> {
+    let line_size = (width as usize * bits_per_pixel).div_ceil(8);
+    let mut out = vec![0u8; line_size * height as usize];
+    let mut offset = 0usize;
+
+    for pass in &PASSES {
+        let pass_width = pass_dimension(width, pass.x_origin, pass.x_stride);
+        let pass_height = pass_dimension(height, pass.y_origin, pass.y_stride);
+
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+
+        let pass_line_size = (pass_width as usize * bits_per_pixel).div_ceil(8);
+        let pass_stride = pass_line_size + 1;
+        let pass_bytes = pass_stride * pass_height as usize;
+
+        let pass_data = raw
+            .get(offset..offset + pass_bytes)
+            .ok_or(DecodeError::Adam7PassTooShort)?;
+        offset += pass_bytes;
+
+        let pass_pixels = unfilter(pass_data, pass_line_size, pass_height as usize, bpp)?;
+
+        for row in 0..pass_height {
+            let dst_row = (pass.y_origin + row * pass.y_stride) as usize;
+            let src_row = &pass_pixels
+                [row as usize * pass_line_size..(row as usize + 1) * pass_line_size];
+            let dst_row_bytes = &mut out[dst_row * line_size..(dst_row + 1) * line_size];
+
+            for col in 0..pass_width {
+                let dst_col = (pass.x_origin + col * pass.x_stride) as usize;
+                copy_pixel(src_row, col as usize, dst_row_bytes, dst_col, bits_per_pixel);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Copies the `bits_per_pixel` bits of pixel `src_col` from `src_row` into
+/// pixel `dst_col` of `dst_row`, bit-for-bit, so this works for sub-byte
+/// bit depths as well as byte-aligned ones.
+fn copy_pixel(src_row: &[u8], src_col: usize, dst_row: &mut [u8], dst_col: usize, bits_per_pixel: usize) {
+    let src_bit_offset = src_col * bits_per_pixel;
+    let dst_bit_offset = dst_col * bits_per_pixel;
+
+    for i in 0..bits_per_pixel {
+        let src_byte = (src_bit_offset + i) / 8;
+        let src_bit = 7 - (src_bit_offset + i) % 8;
+        let bit = (src_row[src_byte] >> src_bit) & 1;
+
+        let dst_byte = (dst_bit_offset + i) / 8;
+        let dst_bit = 7 - (dst_bit_offset + i) % 8;
+
+        if bit == 1 {
+            dst_row[dst_byte] |= 1 << dst_bit;
+        } else {
+            dst_row[dst_byte] &= !(1 << dst_bit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reassembles_a_4x4_8bit_interlaced_image() {
+        // A 4x4, 8-bit grayscale image with pixel values 0..16 in row-major
+        // order, hand-split into its 7 Adam7 passes (passes 1 and 2 are
+        // empty at this size and contribute no bytes), each row prefixed
+        // with filter type 0 (None).
+        #[rustfmt::skip]
+        let raw = [
+            0, 0,                  // pass 0: (0,0) = 0
+            0, 2,                  // pass 3: (2,0) = 2
+            0, 8, 10,              // pass 4: (0,2),(2,2) = 8, 10
+            0, 1, 3,               // pass 5 row 0: (1,0),(3,0) = 1, 3
+            0, 9, 11,              // pass 5 row 1: (1,2),(3,2) = 9, 11
+            0, 4, 5, 6, 7,         // pass 6 row 0: (0,1)..(3,1) = 4, 5, 6, 7
+            0, 12, 13, 14, 15,     // pass 6 row 1: (0,3)..(3,3) = 12, 13, 14, 15
+        ];
+
+        let pixels = deinterlace(&raw, 4, 4, 8, 1).unwrap();
+        assert_eq!(pixels, (0..16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn rejects_a_raw_stream_truncated_mid_pass() {
+        let raw = [0u8, 0]; // only pass 0's byte, missing the rest
+        assert!(matches!(
+            deinterlace(&raw, 4, 4, 8, 1),
+            Err(DecodeError::Adam7PassTooShort)
+        ));
+    }
+}
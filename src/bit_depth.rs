@@ -0,0 +1,32 @@
+use crate::error::DecodeError;
+
+/// https://www.w3.org/TR/2003/REC-PNG-20031110/#11IHDR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    _1 = 1,
+    _2 = 2,
+    _4 = 4,
+    _8 = 8,
+    _16 = 16,
+}
+
+impl BitDepth {
+    pub fn bits(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl TryFrom<u8> for BitDepth {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(BitDepth::_1),
+            2 => Ok(BitDepth::_2),
+            4 => Ok(BitDepth::_4),
+            8 => Ok(BitDepth::_8),
+            16 => Ok(BitDepth::_16),
+            _ => Err(DecodeError::InvalidBitDepth(value)),
+        }
+    }
+}
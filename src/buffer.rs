@@ -0,0 +1,51 @@
+use crate::error::DecodeError;
+
+/// A cursor over the raw PNG bytes, used by `Decoder` to pull out
+/// fixed-width fields without ever copying the whole file.
+pub struct Buffer {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl Buffer {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Current read offset, usable as a checkpoint for `slice_from`.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The bytes consumed since `start` (as returned by `pos`), without
+    /// advancing the cursor. Used to recover the raw bytes of a field after
+    /// it has already been parsed, e.g. for CRC verification.
+    pub fn slice_from(&self, start: usize) -> &[u8] {
+        &self.bytes[start..self.pos]
+    }
+
+    pub fn read_n(&mut self, n: usize) -> Result<&[u8], DecodeError> {
+        let start = self.pos;
+        let end = start
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEof)?;
+
+        self.pos = end;
+        Ok(&self.bytes[start..end])
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_n(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        let bytes = self.read_n(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = self.read_n(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::bit_depth::BitDepth;
+use crate::color_type::ColorType;
+use crate::error::DecodeError;
+use crate::interlace_method::InterlaceMethod;
+
+// Chunk type names are fixed by the PNG spec's four-letter case convention
+// (e.g. tEXt, pHYs), not Rust's type-naming convention.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkType {
+    IHDR,
+    PLTE,
+    IDAT,
+    IEND,
+    gAMA,
+    bKGD,
+    tRNS,
+    tEXt,
+    zTXt,
+    iTXt,
+    pHYs,
+    tIME,
+    sRGB,
+    cHRM,
+}
+
+impl TryFrom<&[u8]> for ChunkType {
+    type Error = DecodeError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            b"IHDR" => Ok(ChunkType::IHDR),
+            b"PLTE" => Ok(ChunkType::PLTE),
+            b"IDAT" => Ok(ChunkType::IDAT),
+            b"IEND" => Ok(ChunkType::IEND),
+            b"gAMA" => Ok(ChunkType::gAMA),
+            b"bKGD" => Ok(ChunkType::bKGD),
+            b"tRNS" => Ok(ChunkType::tRNS),
+            b"tEXt" => Ok(ChunkType::tEXt),
+            b"zTXt" => Ok(ChunkType::zTXt),
+            b"iTXt" => Ok(ChunkType::iTXt),
+            b"pHYs" => Ok(ChunkType::pHYs),
+            b"tIME" => Ok(ChunkType::tIME),
+            b"sRGB" => Ok(ChunkType::sRGB),
+            b"cHRM" => Ok(ChunkType::cHRM),
+            _ => Err(DecodeError::InvalidChunkType),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Chunk {
+    pub length: u32,
+    pub ty: ChunkType,
+    pub data: ChunkData,
+    pub crc: u32,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug)]
+pub enum ChunkData {
+    IHDR {
+        width: u32,
+        height: u32,
+        bit_depth: BitDepth,
+        color_type: ColorType,
+        compression_method: u8,
+        filter_method: u8,
+        interlace_method: InterlaceMethod,
+    },
+    PLTE(Vec<(u8, u8, u8)>),
+    IDAT(Vec<u8>),
+    IEND,
+    gAMA {
+        image_gamma: f64,
+    },
+    bKGD(BackgroundData),
+    tRNS(TransparencyData),
+    tEXt {
+        keyword: String,
+        text: String,
+    },
+    zTXt {
+        keyword: String,
+        text: String,
+    },
+    iTXt {
+        keyword: String,
+        compression_flag: u8,
+        compression_method: u8,
+        language_tag: String,
+        translated_keyword: String,
+        text: String,
+    },
+    pHYs {
+        pixels_per_unit_x: u32,
+        pixels_per_unit_y: u32,
+        unit_specifier: u8,
+    },
+    tIME {
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    },
+    sRGB {
+        rendering_intent: u8,
+    },
+    cHRM {
+        white_point_x: f64,
+        white_point_y: f64,
+        red_x: f64,
+        red_y: f64,
+        green_x: f64,
+        green_y: f64,
+        blue_x: f64,
+        blue_y: f64,
+    },
+}
+
+#[derive(Debug)]
+pub enum BackgroundData {
+    Grayscale(u16),
+    RGB((u16, u16, u16)),
+    PaletteIndex(u8),
+}
+
+#[derive(Debug)]
+pub enum TransparencyData {
+    Graysample(u16),
+    RGB((u16, u16, u16)),
+    PaletteIndices(Vec<u8>),
+}
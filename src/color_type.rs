@@ -0,0 +1,39 @@
+use crate::error::DecodeError;
+
+/// https://www.w3.org/TR/2003/REC-PNG-20031110/#6Colour-values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    _0 = 0,
+    _2 = 2,
+    _3 = 3,
+    _4 = 4,
+    _6 = 6,
+}
+
+impl ColorType {
+    /// Number of samples per pixel, used to compute `bpp` during unfiltering.
+    pub fn channels(&self) -> u8 {
+        match self {
+            ColorType::_0 => 1,
+            ColorType::_2 => 3,
+            ColorType::_3 => 1,
+            ColorType::_4 => 2,
+            ColorType::_6 => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ColorType::_0),
+            2 => Ok(ColorType::_2),
+            3 => Ok(ColorType::_3),
+            4 => Ok(ColorType::_4),
+            6 => Ok(ColorType::_6),
+            _ => Err(DecodeError::InvalidColorType(value)),
+        }
+    }
+}
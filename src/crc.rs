@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+const POLYNOMIAL: u32 = 0xEDB88320;
+
+fn table() -> &'static [u32; 256] {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    POLYNOMIAL ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        table
+    })
+}
+
+/// PNG's CRC-32: reflected, polynomial 0xEDB88320, seeded with all-ones and
+/// complemented on the way out. Computed over the chunk type bytes followed
+/// by the chunk data bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finalize()
+}
+
+/// Incremental version of `crc32`, for computing a chunk's checksum as its
+/// bytes arrive piecemeal (e.g. across multiple `StreamingDecoder::update`
+/// calls) instead of needing them all in one slice.
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self { crc: 0xFFFFFFFF }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        let table = table();
+        for &byte in bytes {
+            self.crc = table[((self.crc ^ u32::from(byte)) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    pub fn finalize(&self) -> u32 {
+        self.crc ^ 0xFFFFFFFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_known_vectors() {
+        // https://www.w3.org/TR/2003/REC-PNG-20031110/#D-CRCAppendix lists
+        // IEND's CRC for a zero-length chunk.
+        assert_eq!(crc32(b"IEND"), 0xAE426082);
+        assert_eq!(crc32(b""), 0x00000000);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+}
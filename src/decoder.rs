@@ -1,42 +1,183 @@
+use std::collections::HashSet;
+
+use crate::adam7;
 use crate::bit_depth::BitDepth;
 use crate::buffer::Buffer;
 use crate::chunk::{BackgroundData, Chunk, ChunkData, ChunkType, TransparencyData};
 use crate::color_type::ColorType;
+use crate::crc::crc32;
 use crate::error::DecodeError;
+use crate::filter::unfilter;
+use crate::inflate::zlib_inflate;
 use crate::interlace_method::InterlaceMethod;
+use crate::limits::Limits;
+use crate::output_info::OutputInfo;
+
+/// Chunks the spec only allows a single instance of.
+/// https://www.w3.org/TR/2003/REC-PNG-20031110/#5ChunkOrdering
+pub(crate) const SINGLE_INSTANCE_CHUNKS: [ChunkType; 10] = [
+    ChunkType::IHDR,
+    ChunkType::PLTE,
+    ChunkType::gAMA,
+    ChunkType::bKGD,
+    ChunkType::tRNS,
+    ChunkType::pHYs,
+    ChunkType::tIME,
+    ChunkType::sRGB,
+    ChunkType::cHRM,
+    ChunkType::IEND,
+];
+
+/// Tracks what's been seen so far so `read_chunk` can enforce the spec's
+/// structural rules (IHDR first, IEND last, PLTE before IDAT, etc.) as
+/// chunks stream in one at a time.
+#[derive(Default)]
+pub(crate) struct ChunkOrderState {
+    chunk_count: usize,
+    seen_once: HashSet<ChunkType>,
+    seen_iend: bool,
+    seen_idat: bool,
+    idat_run_closed: bool,
+}
+
+impl ChunkOrderState {
+    pub(crate) fn check(&mut self, ty: ChunkType) -> Result<(), DecodeError> {
+        if self.seen_iend {
+            return Err(DecodeError::ChunkAfterIEND);
+        }
+
+        if self.chunk_count == 0 && ty != ChunkType::IHDR {
+            return Err(DecodeError::MissingIHDR);
+        }
+
+        if SINGLE_INSTANCE_CHUNKS.contains(&ty) && !self.seen_once.insert(ty) {
+            return Err(DecodeError::DuplicateChunk { ty });
+        }
+
+        if ty == ChunkType::PLTE && self.seen_idat {
+            return Err(DecodeError::PlteAfterFirstIdat);
+        }
+
+        match ty {
+            ChunkType::IDAT => {
+                if self.idat_run_closed {
+                    return Err(DecodeError::NonConsecutiveIDAT);
+                }
+                self.seen_idat = true;
+            }
+            ChunkType::IEND => self.seen_iend = true,
+            _ => {
+                if self.seen_idat {
+                    self.idat_run_closed = true;
+                }
+            }
+        }
+
+        self.chunk_count += 1;
+        Ok(())
+    }
+}
 
 pub struct Decoder {
     buffer: Buffer,
     chunks: Vec<Chunk>,
+    verify_crc: bool,
+    order: ChunkOrderState,
+    limits: Limits,
+    bytes_allocated: u64,
 }
 
-const PNG_SIGNATURE: &[u8] = &[137, 80, 78, 71, 13, 10, 26, 10];
+pub(crate) const PNG_SIGNATURE: &[u8] = &[137, 80, 78, 71, 13, 10, 26, 10];
 impl Decoder {
     pub fn new(bytes: Vec<u8>) -> Self {
+        Self::with_limits(bytes, Limits::default())
+    }
+
+    /// Like `new`, but with caller-supplied resource limits, for decoding
+    /// untrusted PNGs without letting a crafted header or chunk length
+    /// force an unbounded allocation.
+    pub fn with_limits(bytes: Vec<u8>, limits: Limits) -> Self {
         Self {
             buffer: Buffer::new(bytes),
             chunks: Vec::new(),
+            verify_crc: true,
+            order: ChunkOrderState::default(),
+            limits,
+            bytes_allocated: 0,
+        }
+    }
+
+    /// Toggle CRC-32 verification of chunk data. Enabled by default; fuzzing
+    /// and recovery callers that want to read past a corrupted chunk can
+    /// turn this off.
+    pub fn set_verify_crc(&mut self, verify_crc: bool) {
+        self.verify_crc = verify_crc;
+    }
+
+    /// Accounts for `additional` bytes about to be allocated for chunk data,
+    /// erroring out before the allocation happens if it would exceed
+    /// `limits.max_total_bytes`.
+    fn check_allocation(&mut self, additional: usize) -> Result<(), DecodeError> {
+        self.bytes_allocated = self.bytes_allocated.saturating_add(additional as u64);
+
+        if self.bytes_allocated > self.limits.max_total_bytes {
+            return Err(DecodeError::LimitExceeded);
         }
+
+        Ok(())
     }
 
-    // TODO we should actually be returning some sort of like data structure
-    // representing all that was decoded
-    pub fn decode(&mut self) -> Result<(), DecodeError> {
+    pub fn decode(&mut self) -> Result<(OutputInfo, Vec<u8>), DecodeError> {
         self.read_signature()?;
         self.read_chunk()?;
 
-        // TODO: check that the first chunk is IHDR
-
         // TODO probably dont force unwrap?
         while self.chunks.last().unwrap().ty != ChunkType::IEND {
             self.read_chunk()?;
         }
 
+        let (width, height, bit_depth, color_type, interlace_method) =
+            match &self.chunks.first().unwrap().data {
+                ChunkData::IHDR {
+                    width,
+                    height,
+                    bit_depth,
+                    color_type,
+                    interlace_method,
+                    ..
+                } => (*width, *height, *bit_depth, *color_type, *interlace_method),
+                _ => unreachable!("ChunkOrderState guarantees the first chunk is IHDR"),
+            };
+
+        let mut compressed = Vec::new();
         for chunk in self.chunks.iter() {
-            println!("{:#?}", chunk);
+            if let ChunkData::IDAT(bytes) = &chunk.data {
+                compressed.extend_from_slice(bytes);
+            }
         }
 
-        Ok(())
+        let raw = zlib_inflate(&compressed, &mut |n| self.check_allocation(n))?;
+
+        let channels = color_type.channels() as usize;
+        let bits_per_pixel = channels * bit_depth.bits() as usize;
+        let bpp = bits_per_pixel.div_ceil(8);
+        let line_size = (width as usize * bits_per_pixel).div_ceil(8);
+
+        let pixels = match interlace_method {
+            InterlaceMethod::None => unfilter(&raw, line_size, height as usize, bpp)?,
+            InterlaceMethod::Adam7 => adam7::deinterlace(&raw, width, height, bits_per_pixel, bpp)?,
+        };
+
+        Ok((
+            OutputInfo {
+                width,
+                height,
+                color_type,
+                bit_depth,
+                line_size,
+            },
+            pixels,
+        ))
     }
 
     fn get_color_type(&self) -> ColorType {
@@ -64,7 +205,9 @@ impl Decoder {
     //https://www.w3.org/TR/2003/REC-PNG-20031110/#table51
     fn read_chunk(&mut self) -> Result<(), DecodeError> {
         let length = self.buffer.read_u32()?;
+        let covered_start = self.buffer.pos();
         let ty: ChunkType = self.buffer.read_n(4)?.try_into()?;
+        self.order.check(ty)?;
 
         let data = match ty {
             ChunkType::IHDR => self.read_ihdr_chunk_data(length)?,
@@ -73,11 +216,28 @@ impl Decoder {
             ChunkType::gAMA => self.read_gama_chunk_data(length)?,
             ChunkType::PLTE => self.read_plte_chunk_data(length)?,
             ChunkType::bKGD => self.read_bkgd_chunk_data(length)?,
-            ChunkType::tRNS => self.read_trns_chunk_data(length)?
+            ChunkType::tRNS => self.read_trns_chunk_data(length)?,
+            ChunkType::tEXt => self.read_text_chunk_data(length)?,
+            ChunkType::zTXt => self.read_ztxt_chunk_data(length)?,
+            ChunkType::iTXt => self.read_itxt_chunk_data(length)?,
+            ChunkType::pHYs => self.read_phys_chunk_data(length)?,
+            ChunkType::tIME => self.read_time_chunk_data(length)?,
+            ChunkType::sRGB => self.read_srgb_chunk_data(length)?,
+            ChunkType::cHRM => self.read_chrm_chunk_data(length)?,
         };
 
+        let covered = self.buffer.slice_from(covered_start);
+        let computed = crc32(covered);
         let crc = self.buffer.read_u32()?;
 
+        if self.verify_crc && computed != crc {
+            return Err(DecodeError::CrcMismatch {
+                ty,
+                expected: crc,
+                computed,
+            });
+        }
+
         self.chunks.push(Chunk {
             length,
             ty,
@@ -96,6 +256,11 @@ impl Decoder {
 
         let width = self.buffer.read_u32()?;
         let height = self.buffer.read_u32()?;
+
+        if u64::from(width) * u64::from(height) > self.limits.max_pixels {
+            return Err(DecodeError::LimitExceeded);
+        }
+
         let bit_depth: BitDepth = self.buffer.read_u8()?.try_into()?;
         let color_type: ColorType = self.buffer.read_u8()?.try_into()?;
 
@@ -107,7 +272,6 @@ impl Decoder {
 
         let interlace_method: InterlaceMethod = self.buffer.read_u8()?.try_into()?;
 
-        println!("- read ihdr chunk data");
         Ok(ChunkData::IHDR {
             width,
             height,
@@ -121,13 +285,14 @@ impl Decoder {
 
     fn read_idat_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
         let length: usize = length.try_into().unwrap();
+        self.check_allocation(length)?;
         let bytes = Vec::from(self.buffer.read_n(length)?);
 
         Ok(ChunkData::IDAT(bytes))
     }
 
-    fn read_gama_chunk_data(&mut self, _length: u32) -> Result<ChunkData, DecodeError> {
-        // TODO: check length is 4
+    fn read_gama_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        self.expect_chunk_length(ChunkType::gAMA, length, 4)?;
 
         // 11.3.3.2:
         //  The value is encoded as a four-byte PNG unsigned integer, representing gamma times 100000
@@ -138,6 +303,7 @@ impl Decoder {
 
     fn read_plte_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
         let length: usize = length.try_into().unwrap();
+        self.check_allocation(length)?;
         let mut entries = Vec::with_capacity(length / 3);
 
         for _ in 0..(length / 3) {
@@ -190,6 +356,7 @@ impl Decoder {
             ColorType::_3 => {
                 // TODO - check that there arent more entries than palette entries
 
+                self.check_allocation(length)?;
                 let mut indices = Vec::with_capacity(length);
                 for _ in 0..length {
                     let index = self.buffer.read_u8()?;
@@ -204,4 +371,419 @@ impl Decoder {
 
         Ok(ChunkData::tRNS(inner))
     }
+
+    /// Rejects a fixed-size chunk whose declared length doesn't match the
+    /// size the spec requires, the same way `read_ihdr_chunk_data` already
+    /// does for IHDR. Without this, a chunk with the wrong declared length
+    /// would have its fixed fields read right past (or short of) its real
+    /// boundary, relying on the following chunk's bytes to happen to make
+    /// the CRC check pass.
+    fn expect_chunk_length(
+        &self,
+        ty: ChunkType,
+        length: u32,
+        expected: u32,
+    ) -> Result<(), DecodeError> {
+        if length != expected {
+            return Err(DecodeError::InvalidChunkLength {
+                ty,
+                expected,
+                actual: length,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The number of bytes left in a chunk of `length` bytes whose body
+    /// started at `start`, given how far `self.buffer` has advanced since
+    /// then. A malformed chunk can claim a `length` too small to hold the
+    /// fixed-size fields a chunk type requires after its keyword, which
+    /// would otherwise make this subtraction overflow; returns
+    /// `MalformedTextChunk` instead of panicking in that case.
+    fn remaining_in_chunk(&self, start: usize, length: usize) -> Result<usize, DecodeError> {
+        length
+            .checked_sub(self.buffer.pos() - start)
+            .ok_or(DecodeError::MalformedTextChunk)
+    }
+
+    /// Reads up to `max` bytes looking for a NUL terminator, returning the
+    /// bytes before it. Used for the keyword/language-tag fields of the text
+    /// chunks, which are bounded by the enclosing chunk's length.
+    fn read_null_terminated(&mut self, max: usize) -> Result<Vec<u8>, DecodeError> {
+        let mut bytes = Vec::new();
+
+        loop {
+            if bytes.len() >= max {
+                return Err(DecodeError::MalformedTextChunk);
+            }
+
+            match self.buffer.read_u8()? {
+                0 => return Ok(bytes),
+                b => bytes.push(b),
+            }
+        }
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11tEXt
+    fn read_text_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        let length = length as usize;
+        let start = self.buffer.pos();
+
+        let keyword_bytes = self.read_null_terminated(length)?;
+        let keyword = keyword_bytes.iter().map(|&b| b as char).collect();
+
+        let remaining = self.remaining_in_chunk(start, length)?;
+        self.check_allocation(remaining)?;
+        let text = self
+            .buffer
+            .read_n(remaining)?
+            .iter()
+            .map(|&b| b as char)
+            .collect();
+
+        Ok(ChunkData::tEXt { keyword, text })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11zTXt
+    fn read_ztxt_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        let length = length as usize;
+        let start = self.buffer.pos();
+
+        let keyword_bytes = self.read_null_terminated(length)?;
+        let keyword = keyword_bytes.iter().map(|&b| b as char).collect();
+
+        if self.remaining_in_chunk(start, length)? < 1 {
+            return Err(DecodeError::MalformedTextChunk);
+        }
+        // TODO: Add proper support for compression_method field
+        self.buffer.read_u8()?;
+
+        let remaining = self.remaining_in_chunk(start, length)?;
+        self.check_allocation(remaining)?;
+        let compressed = self.buffer.read_n(remaining)?.to_vec();
+        let inflated = zlib_inflate(&compressed, &mut |n| self.check_allocation(n))?;
+        let text = inflated.iter().map(|&b| b as char).collect();
+
+        Ok(ChunkData::zTXt { keyword, text })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11iTXt
+    fn read_itxt_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        let length = length as usize;
+        let start = self.buffer.pos();
+
+        let keyword_bytes = self.read_null_terminated(length)?;
+        let keyword = keyword_bytes.iter().map(|&b| b as char).collect();
+
+        if self.remaining_in_chunk(start, length)? < 2 {
+            return Err(DecodeError::MalformedTextChunk);
+        }
+        let compression_flag = self.buffer.read_u8()?;
+        let compression_method = self.buffer.read_u8()?;
+
+        let remaining_for_tag = self.remaining_in_chunk(start, length)?;
+        let language_tag_bytes = self.read_null_terminated(remaining_for_tag)?;
+        let language_tag = language_tag_bytes.iter().map(|&b| b as char).collect();
+
+        let remaining_for_keyword = self.remaining_in_chunk(start, length)?;
+        let translated_keyword_bytes = self.read_null_terminated(remaining_for_keyword)?;
+        let translated_keyword = String::from_utf8(translated_keyword_bytes)
+            .map_err(|_| DecodeError::MalformedTextChunk)?;
+
+        let remaining = self.remaining_in_chunk(start, length)?;
+        self.check_allocation(remaining)?;
+        let text_bytes = self.buffer.read_n(remaining)?.to_vec();
+
+        let text = if compression_flag == 0 {
+            String::from_utf8(text_bytes).map_err(|_| DecodeError::MalformedTextChunk)?
+        } else {
+            let inflated = zlib_inflate(&text_bytes, &mut |n| self.check_allocation(n))?;
+            String::from_utf8(inflated).map_err(|_| DecodeError::MalformedTextChunk)?
+        };
+
+        Ok(ChunkData::iTXt {
+            keyword,
+            compression_flag,
+            compression_method,
+            language_tag,
+            translated_keyword,
+            text,
+        })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11pHYs
+    fn read_phys_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        self.expect_chunk_length(ChunkType::pHYs, length, 9)?;
+
+        let pixels_per_unit_x = self.buffer.read_u32()?;
+        let pixels_per_unit_y = self.buffer.read_u32()?;
+        let unit_specifier = self.buffer.read_u8()?;
+
+        Ok(ChunkData::pHYs {
+            pixels_per_unit_x,
+            pixels_per_unit_y,
+            unit_specifier,
+        })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11tIME
+    fn read_time_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        self.expect_chunk_length(ChunkType::tIME, length, 7)?;
+
+        let year = self.buffer.read_u16()?;
+        let month = self.buffer.read_u8()?;
+        let day = self.buffer.read_u8()?;
+        let hour = self.buffer.read_u8()?;
+        let minute = self.buffer.read_u8()?;
+        let second = self.buffer.read_u8()?;
+
+        Ok(ChunkData::tIME {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11sRGB
+    fn read_srgb_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        self.expect_chunk_length(ChunkType::sRGB, length, 1)?;
+
+        let rendering_intent = self.buffer.read_u8()?;
+
+        Ok(ChunkData::sRGB { rendering_intent })
+    }
+
+    // https://www.w3.org/TR/2003/REC-PNG-20031110/#11cHRM
+    fn read_chrm_chunk_data(&mut self, length: u32) -> Result<ChunkData, DecodeError> {
+        self.expect_chunk_length(ChunkType::cHRM, length, 32)?;
+
+        let mut next = || -> Result<f64, DecodeError> {
+            Ok(f64::from(self.buffer.read_u32()?) / f64::from(100000))
+        };
+
+        let white_point_x = next()?;
+        let white_point_y = next()?;
+        let red_x = next()?;
+        let red_y = next()?;
+        let green_x = next()?;
+        let green_y = next()?;
+        let blue_x = next()?;
+        let blue_y = next()?;
+
+        Ok(ChunkData::cHRM {
+            white_point_x,
+            white_point_y,
+            red_x,
+            red_y,
+            green_x,
+            green_y,
+            blue_x,
+            blue_y,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_well_formed_sequence() {
+        let mut order = ChunkOrderState::default();
+        assert!(order.check(ChunkType::IHDR).is_ok());
+        assert!(order.check(ChunkType::PLTE).is_ok());
+        assert!(order.check(ChunkType::IDAT).is_ok());
+        assert!(order.check(ChunkType::IDAT).is_ok());
+        assert!(order.check(ChunkType::IEND).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_first_chunk_that_isnt_ihdr() {
+        let mut order = ChunkOrderState::default();
+        assert!(matches!(
+            order.check(ChunkType::PLTE),
+            Err(DecodeError::MissingIHDR)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_duplicated_single_instance_chunk() {
+        let mut order = ChunkOrderState::default();
+        order.check(ChunkType::IHDR).unwrap();
+        assert!(matches!(
+            order.check(ChunkType::IHDR),
+            Err(DecodeError::DuplicateChunk {
+                ty: ChunkType::IHDR
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_chunk_after_iend() {
+        let mut order = ChunkOrderState::default();
+        order.check(ChunkType::IHDR).unwrap();
+        order.check(ChunkType::IEND).unwrap();
+        assert!(matches!(
+            order.check(ChunkType::tEXt),
+            Err(DecodeError::ChunkAfterIEND)
+        ));
+    }
+
+    #[test]
+    fn rejects_plte_after_the_first_idat() {
+        let mut order = ChunkOrderState::default();
+        order.check(ChunkType::IHDR).unwrap();
+        order.check(ChunkType::IDAT).unwrap();
+        assert!(matches!(
+            order.check(ChunkType::PLTE),
+            Err(DecodeError::PlteAfterFirstIdat)
+        ));
+    }
+
+    #[test]
+    fn rejects_non_consecutive_idat_chunks() {
+        let mut order = ChunkOrderState::default();
+        order.check(ChunkType::IHDR).unwrap();
+        order.check(ChunkType::IDAT).unwrap();
+        order.check(ChunkType::tEXt).unwrap();
+        assert!(matches!(
+            order.check(ChunkType::IDAT),
+            Err(DecodeError::NonConsecutiveIDAT)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_ztxt_chunk_too_short_for_its_compression_method_byte() {
+        // A 1-byte zTXt chunk containing just a NUL keyword terminator has
+        // no room left for the required compression_method byte.
+        let mut decoder = Decoder::new(vec![0u8]);
+        assert!(matches!(
+            decoder.read_ztxt_chunk_data(1),
+            Err(DecodeError::MalformedTextChunk)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_phys_chunk_with_the_wrong_declared_length() {
+        // A pHYs chunk declaring length 0, even though 9 real data bytes
+        // follow it, must be rejected rather than silently reading past its
+        // declared boundary into whatever comes next.
+        let mut decoder = Decoder::new(vec![0u8; 9]);
+        assert!(matches!(
+            decoder.read_phys_chunk_data(0),
+            Err(DecodeError::InvalidChunkLength {
+                ty: ChunkType::pHYs,
+                expected: 9,
+                actual: 0,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_gama_chunk_with_the_wrong_declared_length() {
+        let mut decoder = Decoder::new(vec![0u8; 4]);
+        assert!(matches!(
+            decoder.read_gama_chunk_data(3),
+            Err(DecodeError::InvalidChunkLength {
+                ty: ChunkType::gAMA,
+                expected: 4,
+                actual: 3,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_time_chunk_with_the_wrong_declared_length() {
+        let mut decoder = Decoder::new(vec![0u8; 7]);
+        assert!(matches!(
+            decoder.read_time_chunk_data(6),
+            Err(DecodeError::InvalidChunkLength {
+                ty: ChunkType::tIME,
+                expected: 7,
+                actual: 6,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_srgb_chunk_with_the_wrong_declared_length() {
+        let mut decoder = Decoder::new(vec![0u8; 1]);
+        assert!(matches!(
+            decoder.read_srgb_chunk_data(0),
+            Err(DecodeError::InvalidChunkLength {
+                ty: ChunkType::sRGB,
+                expected: 1,
+                actual: 0,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_chrm_chunk_with_the_wrong_declared_length() {
+        let mut decoder = Decoder::new(vec![0u8; 32]);
+        assert!(matches!(
+            decoder.read_chrm_chunk_data(31),
+            Err(DecodeError::InvalidChunkLength {
+                ty: ChunkType::cHRM,
+                expected: 32,
+                actual: 31,
+            })
+        ));
+    }
+
+    fn push_chunk(png: &mut Vec<u8>, ty: &[u8; 4], data: &[u8]) {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(ty);
+        png.extend_from_slice(data);
+        let mut crc_input = ty.to_vec();
+        crc_input.extend_from_slice(data);
+        png.extend_from_slice(&crc32(&crc_input).to_be_bytes());
+    }
+
+    /// Builds a minimal 2x2, 8-bit grayscale PNG whose single IDAT chunk
+    /// holds a hand-assembled zlib stream wrapping one stored (uncompressed)
+    /// DEFLATE block, so the test doesn't depend on a real encoder.
+    fn minimal_grayscale_png() -> Vec<u8> {
+        let mut png = vec![137, 80, 78, 71, 13, 10, 26, 10];
+
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&2u32.to_be_bytes()); // width
+        ihdr.extend_from_slice(&2u32.to_be_bytes()); // height
+        ihdr.push(8); // bit depth
+        ihdr.push(0); // color type: grayscale
+        ihdr.push(0); // compression method
+        ihdr.push(0); // filter method
+        ihdr.push(0); // interlace method: none
+        push_chunk(&mut png, b"IHDR", &ihdr);
+
+        // Two unfiltered (filter type 0) scanlines of raw pixel data: 10, 20
+        // and 30, 40.
+        let raw = [0u8, 10, 20, 0, 30, 40];
+
+        let mut idat = vec![0x78, 0x9C]; // zlib header: deflate, default window
+        idat.push(0x01); // BFINAL=1, BTYPE=00 (stored), rest of byte is padding
+        idat.extend_from_slice(&(raw.len() as u16).to_le_bytes()); // LEN
+        idat.extend_from_slice(&(!(raw.len() as u16)).to_le_bytes()); // NLEN
+        idat.extend_from_slice(&raw);
+        idat.extend_from_slice(&[0, 0, 0, 0]); // adler32, unchecked
+        push_chunk(&mut png, b"IDAT", &idat);
+
+        push_chunk(&mut png, b"IEND", &[]);
+
+        png
+    }
+
+    #[test]
+    fn decodes_a_stored_deflate_block_and_unfilters_it() {
+        let mut decoder = Decoder::new(minimal_grayscale_png());
+        let (info, pixels) = decoder.decode().unwrap();
+
+        assert_eq!(info.width, 2);
+        assert_eq!(info.height, 2);
+        assert_eq!(pixels, vec![10, 20, 30, 40]);
+    }
 }
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::chunk::ChunkType;
+
+#[derive(Debug)]
+pub enum DecodeError {
+    MissingSignature,
+    UnexpectedEof,
+    InvalidIHDRLength,
+    InvalidChunkLength {
+        ty: ChunkType,
+        expected: u32,
+        actual: u32,
+    },
+    InvalidBitDepth(u8),
+    InvalidColorType(u8),
+    InvalidInterlaceMethod(u8),
+    InvalidChunkType,
+    UnexpectedtRNSChunk,
+    CrcMismatch {
+        ty: ChunkType,
+        expected: u32,
+        computed: u32,
+    },
+    MissingIHDR,
+    DuplicateChunk {
+        ty: ChunkType,
+    },
+    ChunkAfterIEND,
+    PlteAfterFirstIdat,
+    NonConsecutiveIDAT,
+    LimitExceeded,
+    InvalidZlibStream,
+    InvalidDeflateStream,
+    InvalidFilterType(u8),
+    MalformedTextChunk,
+    Adam7PassTooShort,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::MissingSignature => write!(f, "missing or invalid PNG signature"),
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of file"),
+            DecodeError::InvalidIHDRLength => write!(f, "IHDR chunk did not have a length of 13"),
+            DecodeError::InvalidChunkLength {
+                ty,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:?} chunk should have a length of {}, but was {}",
+                ty, expected, actual
+            ),
+            DecodeError::InvalidBitDepth(b) => write!(f, "invalid bit depth: {}", b),
+            DecodeError::InvalidColorType(c) => write!(f, "invalid color type: {}", c),
+            DecodeError::InvalidInterlaceMethod(m) => write!(f, "invalid interlace method: {}", m),
+            DecodeError::InvalidChunkType => write!(f, "unrecognized chunk type"),
+            DecodeError::UnexpectedtRNSChunk => {
+                write!(f, "tRNS chunk is not allowed for this color type")
+            }
+            DecodeError::CrcMismatch {
+                ty,
+                expected,
+                computed,
+            } => write!(
+                f,
+                "CRC mismatch in {:?} chunk: expected {:#010x}, computed {:#010x}",
+                ty, expected, computed
+            ),
+            DecodeError::MissingIHDR => write!(f, "first chunk was not IHDR"),
+            DecodeError::DuplicateChunk { ty } => {
+                write!(f, "{:?} may only appear once, but was seen twice", ty)
+            }
+            DecodeError::ChunkAfterIEND => write!(f, "chunk found after IEND"),
+            DecodeError::PlteAfterFirstIdat => {
+                write!(f, "PLTE must precede the first IDAT chunk")
+            }
+            DecodeError::NonConsecutiveIDAT => {
+                write!(f, "IDAT chunks must be consecutive")
+            }
+            DecodeError::LimitExceeded => {
+                write!(f, "decoding exceeded the configured resource limits")
+            }
+            DecodeError::InvalidZlibStream => write!(f, "malformed zlib stream"),
+            DecodeError::InvalidDeflateStream => write!(f, "malformed DEFLATE stream"),
+            DecodeError::InvalidFilterType(ty) => write!(f, "invalid scanline filter type: {}", ty),
+            DecodeError::MalformedTextChunk => {
+                write!(
+                    f,
+                    "text chunk was malformed: missing null terminator, too short for its fields, or invalid UTF-8"
+                )
+            }
+            DecodeError::Adam7PassTooShort => {
+                write!(f, "inflated data ended in the middle of an Adam7 pass")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
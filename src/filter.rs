@@ -0,0 +1,124 @@
+use crate::error::DecodeError;
+
+/// Reverses the PNG scanline filters (section 9 of the spec), given the
+/// inflated IDAT stream, the byte width of one unfiltered scanline, the
+/// image height in scanlines, and `bpp` (bytes per complete pixel, used to
+/// locate the "left" and "upper-left" reference bytes).
+pub fn unfilter(data: &[u8], line_size: usize, height: usize, bpp: usize) -> Result<Vec<u8>, DecodeError> {
+    let stride = line_size + 1;
+    if data.len() < stride * height {
+        return Err(DecodeError::UnexpectedEof);
+    }
+
+    let mut out = Vec::with_capacity(line_size * height);
+    let mut prev_row = vec![0u8; line_size];
+
+    for row in 0..height {
+        let row_start = row * stride;
+        let filter_type = data[row_start];
+        let src = &data[row_start + 1..row_start + 1 + line_size];
+        let mut cur_row = vec![0u8; line_size];
+
+        for i in 0..line_size {
+            let a = if i >= bpp { cur_row[i - bpp] } else { 0 };
+            let b = prev_row[i];
+            let c = if i >= bpp { prev_row[i - bpp] } else { 0 };
+
+            cur_row[i] = match filter_type {
+                0 => src[i],
+                1 => src[i].wrapping_add(a),
+                2 => src[i].wrapping_add(b),
+                3 => src[i].wrapping_add(((u16::from(a) + u16::from(b)) / 2) as u8),
+                4 => src[i].wrapping_add(paeth(a, b, c)),
+                _ => return Err(DecodeError::InvalidFilterType(filter_type)),
+            };
+        }
+
+        out.extend_from_slice(&cur_row);
+        prev_row = cur_row;
+    }
+
+    Ok(out)
+}
+
+// https://www.w3.org/TR/2003/REC-PNG-20031110/#9Filter-type-4-Paeth
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i32::from(a) + i32::from(b) - i32::from(c);
+    let pa = (p - i32::from(a)).abs();
+    let pb = (p - i32::from(b)).abs();
+    let pc = (p - i32::from(c)).abs();
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every case below reconstructs the same two rows (a 3-byte-wide, 1 bpp
+    // image): [10, 20, 30] then [40, 50, 60]. The first row is always stored
+    // with filter type None so its bytes match the expected output directly;
+    // the second row is filtered with the type under test, with the filtered
+    // bytes worked out by hand from the spec's reconstruction formulas so a
+    // mistake in `unfilter` trips the assertion rather than round-tripping.
+
+    #[test]
+    fn unfilters_type_none() {
+        let data = [0, 10, 20, 30, 0, 40, 50, 60];
+        assert_eq!(
+            unfilter(&data, 3, 2, 1).unwrap(),
+            vec![10, 20, 30, 40, 50, 60]
+        );
+    }
+
+    #[test]
+    fn unfilters_type_sub() {
+        let data = [0, 10, 20, 30, 1, 40, 10, 10];
+        assert_eq!(
+            unfilter(&data, 3, 2, 1).unwrap(),
+            vec![10, 20, 30, 40, 50, 60]
+        );
+    }
+
+    #[test]
+    fn unfilters_type_up() {
+        let data = [0, 10, 20, 30, 2, 30, 30, 30];
+        assert_eq!(
+            unfilter(&data, 3, 2, 1).unwrap(),
+            vec![10, 20, 30, 40, 50, 60]
+        );
+    }
+
+    #[test]
+    fn unfilters_type_average() {
+        let data = [0, 10, 20, 30, 3, 35, 20, 20];
+        assert_eq!(
+            unfilter(&data, 3, 2, 1).unwrap(),
+            vec![10, 20, 30, 40, 50, 60]
+        );
+    }
+
+    #[test]
+    fn unfilters_type_paeth() {
+        let data = [0, 10, 20, 30, 4, 30, 10, 10];
+        assert_eq!(
+            unfilter(&data, 3, 2, 1).unwrap(),
+            vec![10, 20, 30, 40, 50, 60]
+        );
+    }
+
+    #[test]
+    fn rejects_an_unknown_filter_type() {
+        let data = [5, 10, 20, 30];
+        assert!(matches!(
+            unfilter(&data, 3, 1, 1),
+            Err(DecodeError::InvalidFilterType(5))
+        ));
+    }
+}
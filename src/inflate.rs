@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use crate::error::DecodeError;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// LSB-first bit reader over a DEFLATE stream, per RFC 1951 section 3.1.1.
+struct BitReader<'a> {
+    data: &'a [u8],
+    byte_pos: usize,
+    bit_pos: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            byte_pos: 0,
+            bit_pos: 0,
+        }
+    }
+
+    fn read_bit(&mut self) -> Result<u32, DecodeError> {
+        let byte = *self.data.get(self.byte_pos).ok_or(DecodeError::UnexpectedEof)?;
+        let bit = u32::from((byte >> self.bit_pos) & 1);
+
+        self.bit_pos += 1;
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+
+        Ok(bit)
+    }
+
+    fn read_bits(&mut self, count: u32) -> Result<u32, DecodeError> {
+        let mut value = 0u32;
+        for i in 0..count {
+            value |= self.read_bit()? << i;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit_pos != 0 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+    }
+
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        self.align_to_byte();
+        let end = self
+            .byte_pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(DecodeError::UnexpectedEof)?;
+
+        let bytes = &self.data[self.byte_pos..end];
+        self.byte_pos = end;
+        Ok(bytes)
+    }
+}
+
+/// Canonical Huffman decode table, keyed by (code length, code value).
+struct HuffmanTable {
+    codes: HashMap<(u8, u16), u16>,
+    max_len: u8,
+}
+
+impl HuffmanTable {
+    /// Builds the canonical codes for a set of per-symbol code lengths, per
+    /// RFC 1951 section 3.2.2.
+    fn from_lengths(lengths: &[u8]) -> Self {
+        let max_len = lengths.iter().copied().max().unwrap_or(0);
+        let mut bl_count = vec![0u32; max_len as usize + 1];
+        for &len in lengths {
+            if len > 0 {
+                bl_count[len as usize] += 1;
+            }
+        }
+
+        let mut code = 0u32;
+        let mut next_code = vec![0u32; max_len as usize + 1];
+        for len in 1..=max_len as usize {
+            code = (code + bl_count[len - 1]) << 1;
+            next_code[len] = code;
+        }
+
+        let mut codes = HashMap::new();
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let assigned = next_code[len as usize];
+            next_code[len as usize] += 1;
+            codes.insert((len, assigned as u16), symbol as u16);
+        }
+
+        Self { codes, max_len }
+    }
+
+    fn decode(&self, br: &mut BitReader) -> Result<u16, DecodeError> {
+        let mut code = 0u16;
+        for len in 1..=self.max_len {
+            code = (code << 1) | br.read_bit()? as u16;
+            if let Some(&symbol) = self.codes.get(&(len, code)) {
+                return Ok(symbol);
+            }
+        }
+
+        Err(DecodeError::InvalidDeflateStream)
+    }
+}
+
+fn fixed_literal_table() -> HuffmanTable {
+    let mut lengths = vec![0u8; 288];
+    lengths[0..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..288].fill(8);
+    HuffmanTable::from_lengths(&lengths)
+}
+
+fn fixed_distance_table() -> HuffmanTable {
+    HuffmanTable::from_lengths(&[5u8; 30])
+}
+
+fn read_dynamic_tables(br: &mut BitReader) -> Result<(HuffmanTable, HuffmanTable), DecodeError> {
+    let hlit = br.read_bits(5)? as usize + 257;
+    let hdist = br.read_bits(5)? as usize + 1;
+    let hclen = br.read_bits(4)? as usize + 4;
+
+    let mut cl_lengths = [0u8; 19];
+    for &slot in CODE_LENGTH_ORDER.iter().take(hclen) {
+        cl_lengths[slot] = br.read_bits(3)? as u8;
+    }
+    let cl_table = HuffmanTable::from_lengths(&cl_lengths);
+
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        match cl_table.decode(br)? {
+            sym @ 0..=15 => lengths.push(sym as u8),
+            16 => {
+                let prev = *lengths.last().ok_or(DecodeError::InvalidDeflateStream)?;
+                let repeat = br.read_bits(2)? + 3;
+                lengths.extend(std::iter::repeat_n(prev, repeat as usize));
+            }
+            17 => {
+                let repeat = br.read_bits(3)? + 3;
+                lengths.extend(std::iter::repeat_n(0, repeat as usize));
+            }
+            18 => {
+                let repeat = br.read_bits(7)? + 11;
+                lengths.extend(std::iter::repeat_n(0, repeat as usize));
+            }
+            _ => return Err(DecodeError::InvalidDeflateStream),
+        }
+    }
+
+    Ok((
+        HuffmanTable::from_lengths(&lengths[0..hlit]),
+        HuffmanTable::from_lengths(&lengths[hlit..hlit + hdist]),
+    ))
+}
+
+fn inflate_block(
+    br: &mut BitReader,
+    lit_table: &HuffmanTable,
+    dist_table: &HuffmanTable,
+    out: &mut Vec<u8>,
+    check_allocation: &mut dyn FnMut(usize) -> Result<(), DecodeError>,
+) -> Result<(), DecodeError> {
+    loop {
+        let symbol = lit_table.decode(br)?;
+
+        match symbol {
+            0..=255 => {
+                check_allocation(1)?;
+                out.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let idx = (symbol - 257) as usize;
+                let length =
+                    LENGTH_BASE[idx] as usize + br.read_bits(u32::from(LENGTH_EXTRA[idx]))? as usize;
+
+                let dist_symbol = dist_table.decode(br)? as usize;
+                let distance = *DIST_BASE
+                    .get(dist_symbol)
+                    .ok_or(DecodeError::InvalidDeflateStream)? as usize
+                    + br.read_bits(u32::from(
+                        *DIST_EXTRA
+                            .get(dist_symbol)
+                            .ok_or(DecodeError::InvalidDeflateStream)?,
+                    ))? as usize;
+
+                if distance > out.len() {
+                    return Err(DecodeError::InvalidDeflateStream);
+                }
+
+                check_allocation(length)?;
+                let start = out.len() - distance;
+                for i in 0..length {
+                    let byte = out[start + i];
+                    out.push(byte);
+                }
+            }
+            _ => return Err(DecodeError::InvalidDeflateStream),
+        }
+    }
+}
+
+/// Inflates a raw DEFLATE stream (RFC 1951), with no zlib/gzip framing.
+///
+/// `check_allocation` is called with the number of additional output bytes
+/// about to be produced, before they're appended, so callers can bail out of
+/// a decompression bomb (e.g. a tiny compressed chunk that expands to
+/// gigabytes) before the allocation happens rather than after.
+pub fn inflate(
+    data: &[u8],
+    check_allocation: &mut dyn FnMut(usize) -> Result<(), DecodeError>,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut br = BitReader::new(data);
+    let mut out = Vec::new();
+
+    loop {
+        let is_final = br.read_bits(1)? == 1;
+        let block_type = br.read_bits(2)?;
+
+        match block_type {
+            0 => {
+                br.align_to_byte();
+                let len = br.read_bytes(2)?;
+                let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+                br.read_bytes(2)?; // one's complement of len, unchecked
+                let stored = br.read_bytes(len)?;
+                check_allocation(stored.len())?;
+                out.extend_from_slice(stored);
+            }
+            1 => inflate_block(
+                &mut br,
+                &fixed_literal_table(),
+                &fixed_distance_table(),
+                &mut out,
+                check_allocation,
+            )?,
+            2 => {
+                let (lit_table, dist_table) = read_dynamic_tables(&mut br)?;
+                inflate_block(&mut br, &lit_table, &dist_table, &mut out, check_allocation)?;
+            }
+            _ => return Err(DecodeError::InvalidDeflateStream),
+        }
+
+        if is_final {
+            break;
+        }
+    }
+
+    Ok(out)
+}
+
+/// Inflates a zlib-wrapped DEFLATE stream (RFC 1950), which is how IDAT and
+/// zTXt/iTXt compressed text payloads are stored. See `inflate` for the
+/// meaning of `check_allocation`.
+pub fn zlib_inflate(
+    data: &[u8],
+    check_allocation: &mut dyn FnMut(usize) -> Result<(), DecodeError>,
+) -> Result<Vec<u8>, DecodeError> {
+    if data.len() < 6 {
+        return Err(DecodeError::InvalidZlibStream);
+    }
+
+    let cmf = data[0];
+    let flg = data[1];
+
+    if cmf & 0x0F != 8 || (u16::from(cmf) * 256 + u16::from(flg)) % 31 != 0 {
+        return Err(DecodeError::InvalidZlibStream);
+    }
+
+    // FDICT (a preset dictionary) is not something encoders emit for PNG.
+    if flg & 0x20 != 0 {
+        return Err(DecodeError::InvalidZlibStream);
+    }
+
+    inflate(&data[2..], check_allocation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_op_check_allocation(_additional: usize) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    /// Writes a DEFLATE bitstream bit by bit, LSB-first within each byte -
+    /// the mirror image of `BitReader`, so tests can hand-assemble the exact
+    /// byte sequences real compressed blocks would produce.
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bit_pos: u32,
+    }
+
+    impl BitWriter {
+        fn new() -> Self {
+            Self {
+                bytes: Vec::new(),
+                bit_pos: 0,
+            }
+        }
+
+        fn push_bit(&mut self, bit: u8) {
+            if self.bit_pos == 0 {
+                self.bytes.push(0);
+            }
+            if bit != 0 {
+                *self.bytes.last_mut().unwrap() |= 1 << self.bit_pos;
+            }
+            self.bit_pos = (self.bit_pos + 1) % 8;
+        }
+
+        /// Writes a raw integer field the way `BitReader::read_bits` expects
+        /// it: least significant bit first.
+        fn push_bits(&mut self, value: u32, count: u32) {
+            for i in 0..count {
+                self.push_bit(((value >> i) & 1) as u8);
+            }
+        }
+
+        /// Writes a canonical Huffman code, most significant bit first - the
+        /// order `HuffmanTable::decode` assembles bits back into a code.
+        fn push_huffman_code(&mut self, code: u16, len: u8) {
+            for i in (0..len).rev() {
+                self.push_bit(((code >> i) & 1) as u8);
+            }
+        }
+
+        fn finish(self) -> Vec<u8> {
+            self.bytes
+        }
+    }
+
+    #[test]
+    fn inflates_a_fixed_huffman_block_with_a_back_reference() {
+        // BFINAL=1, BTYPE=01 (fixed Huffman), then the literal 'A' (fixed
+        // code 0x71, 8 bits), then a length/distance pair (length 3,
+        // distance 1) that copies the 'A' back three times, then the
+        // end-of-block symbol (256). Decodes to "AAAA".
+        let mut w = BitWriter::new();
+        w.push_bits(1, 1); // BFINAL
+        w.push_bits(1, 2); // BTYPE = fixed Huffman
+
+        w.push_huffman_code(0x71, 8); // literal 'A' (65): fixed code 0x30 + 65
+        w.push_huffman_code(0x01, 7); // length symbol 257 (length base 3, no extra bits)
+        w.push_huffman_code(0x00, 5); // distance symbol 0 (distance base 1, no extra bits)
+        w.push_huffman_code(0x00, 7); // end-of-block (symbol 256)
+
+        let data = w.finish();
+        let out = inflate(&data, &mut no_op_check_allocation).unwrap();
+        assert_eq!(out, b"AAAA");
+    }
+
+    #[test]
+    fn inflates_a_dynamic_huffman_block() {
+        // A dynamic block whose literal/length table only assigns codes to
+        // 'A' (65) and end-of-block (256) - a 2-symbol canonical code, each
+        // 1 bit long (codes "0" and "1"). The code-length alphabet used to
+        // transmit that table is itself minimal: symbols 0 and 1 (direct
+        // code length values) plus 18 (an 11-138 run of zero-length
+        // entries), which keeps the by-hand bit assembly tractable.
+        let mut w = BitWriter::new();
+        w.push_bits(1, 1); // BFINAL
+        w.push_bits(2, 2); // BTYPE = dynamic Huffman
+
+        w.push_bits(0, 5); // HLIT: 257 literal/length codes
+        w.push_bits(0, 5); // HDIST: 1 distance code
+        w.push_bits(15, 4); // HCLEN: all 19 code-length codes transmitted
+
+        // Code-length code lengths, in CODE_LENGTH_ORDER: only symbols 18
+        // (len 2), 0 (len 1) and 1 (len 2) are given lengths.
+        let cl_lengths_in_order = [0, 0, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0];
+        for len in cl_lengths_in_order {
+            w.push_bits(len, 3);
+        }
+        // That yields a canonical code-length table of symbol 0 -> "0" (len
+        // 1), symbol 1 -> "10" (len 2), symbol 18 -> "11" (len 2).
+
+        // Literal/length + distance code lengths: 65 zeros (indices 0-64,
+        // via symbol 18 with a run of 65), a literal 1 for 'A' (index 65),
+        // 138 + 52 more zeros (indices 66-255, the max run length is 138),
+        // a literal 1 for end-of-block (index 256), and a final 0 for the
+        // single distance code (index 257, unused by this block).
+        w.push_huffman_code(0b11, 2);
+        w.push_bits(65 - 11, 7);
+        w.push_huffman_code(0b10, 2);
+        w.push_huffman_code(0b11, 2);
+        w.push_bits(138 - 11, 7);
+        w.push_huffman_code(0b11, 2);
+        w.push_bits(52 - 11, 7);
+        w.push_huffman_code(0b10, 2);
+        w.push_huffman_code(0b0, 1);
+
+        // Block data: the literal 'A' (code "0") twice, then end-of-block
+        // (code "1").
+        w.push_huffman_code(0b0, 1);
+        w.push_huffman_code(0b0, 1);
+        w.push_huffman_code(0b1, 1);
+
+        let data = w.finish();
+        let out = inflate(&data, &mut no_op_check_allocation).unwrap();
+        assert_eq!(out, b"AA");
+    }
+}
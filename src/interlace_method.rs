@@ -0,0 +1,20 @@
+use crate::error::DecodeError;
+
+/// https://www.w3.org/TR/2003/REC-PNG-20031110/#8InterlaceMethods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterlaceMethod {
+    None = 0,
+    Adam7 = 1,
+}
+
+impl TryFrom<u8> for InterlaceMethod {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(InterlaceMethod::None),
+            1 => Ok(InterlaceMethod::Adam7),
+            _ => Err(DecodeError::InvalidInterlaceMethod(value)),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+pub mod adam7;
+pub mod bit_depth;
+pub mod buffer;
+pub mod chunk;
+pub mod color_type;
+pub mod crc;
+pub mod decoder;
+pub mod error;
+pub mod filter;
+pub mod inflate;
+pub mod interlace_method;
+pub mod limits;
+pub mod output_info;
+pub mod streaming_decoder;
+
+pub use decoder::Decoder;
+pub use error::DecodeError;
+pub use limits::Limits;
+pub use output_info::OutputInfo;
+pub use streaming_decoder::{Decoded, StreamingDecoder};
@@ -0,0 +1,19 @@
+/// Bounds on how much work/memory a single `decode()` call may spend,
+/// so a crafted header or chunk length can't turn a small file into an
+/// allocation bomb.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Total bytes allocated for chunk data across the whole decode.
+    pub max_total_bytes: u64,
+    /// Maximum `width * height` pixel count allowed by IHDR.
+    pub max_pixels: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 64 * 1024 * 1024,
+            max_pixels: 1 << 26,
+        }
+    }
+}
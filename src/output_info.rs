@@ -0,0 +1,13 @@
+use crate::bit_depth::BitDepth;
+use crate::color_type::ColorType;
+
+/// Describes the pixel buffer `Decoder::decode` reconstructs from IDAT.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputInfo {
+    pub width: u32,
+    pub height: u32,
+    pub color_type: ColorType,
+    pub bit_depth: BitDepth,
+    /// Byte width of one unfiltered scanline.
+    pub line_size: usize,
+}
@@ -0,0 +1,356 @@
+use crate::bit_depth::BitDepth;
+use crate::chunk::ChunkType;
+use crate::color_type::ColorType;
+use crate::crc::Crc32;
+use crate::decoder::{ChunkOrderState, PNG_SIGNATURE};
+use crate::error::DecodeError;
+use crate::interlace_method::InterlaceMethod;
+use crate::limits::Limits;
+
+/// An event produced by `StreamingDecoder::update` as it works its way
+/// through the bytes it's been given so far.
+#[derive(Debug, PartialEq)]
+pub enum Decoded {
+    /// No event to report: the bytes consumed (possibly none) only advanced
+    /// the decoder's internal state.
+    Nothing,
+    Header {
+        width: u32,
+        height: u32,
+        bit_depth: BitDepth,
+        color_type: ColorType,
+        interlace_method: InterlaceMethod,
+    },
+    ChunkBegin(u32, ChunkType),
+    ChunkComplete(ChunkType),
+    /// Some bytes of an IDAT chunk were consumed.
+    ImageData,
+    ImageEnd,
+}
+
+enum State {
+    Signature {
+        read: usize,
+    },
+    ChunkHeader {
+        buf: [u8; 8],
+        read: usize,
+    },
+    ChunkData {
+        ty: ChunkType,
+        length: u32,
+        read: u32,
+        data: Vec<u8>,
+        crc: Crc32,
+    },
+    ChunkCrc {
+        ty: ChunkType,
+        computed: u32,
+        buf: [u8; 4],
+        read: usize,
+    },
+}
+
+/// Accounts for `additional` bytes about to be allocated for chunk data,
+/// erroring out before the allocation happens if it would exceed
+/// `limits.max_total_bytes`. A free function (rather than a `StreamingDecoder`
+/// method) so it can be called while `self.state` is already borrowed by the
+/// match in `update`.
+fn check_allocation(
+    bytes_allocated: &mut u64,
+    limits: &Limits,
+    additional: usize,
+) -> Result<(), DecodeError> {
+    *bytes_allocated = bytes_allocated.saturating_add(additional as u64);
+
+    if *bytes_allocated > limits.max_total_bytes {
+        return Err(DecodeError::LimitExceeded);
+    }
+
+    Ok(())
+}
+
+/// Drives PNG parsing from bytes pushed in arbitrary-sized chunks, instead
+/// of requiring the whole file up front like `Decoder` does. Useful for
+/// feeding a socket or a partial file, reporting progress, and rejecting a
+/// malformed stream before the full image has arrived.
+pub struct StreamingDecoder {
+    state: State,
+    order: ChunkOrderState,
+    limits: Limits,
+    bytes_allocated: u64,
+}
+
+impl Default for StreamingDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingDecoder {
+    pub fn new() -> Self {
+        Self::with_limits(Limits::default())
+    }
+
+    /// Like `new`, but with caller-supplied resource limits, for decoding
+    /// untrusted PNGs without letting a crafted chunk length force an
+    /// unbounded allocation.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            state: State::Signature { read: 0 },
+            order: ChunkOrderState::default(),
+            limits,
+            bytes_allocated: 0,
+        }
+    }
+
+    /// Feeds more input bytes in. Returns how many bytes of `buf` were
+    /// consumed and the event that progress produced; callers should keep
+    /// calling with the unconsumed remainder (and more bytes as they arrive)
+    /// until the whole stream has been fed in.
+    pub fn update(&mut self, buf: &[u8]) -> Result<(usize, Decoded), DecodeError> {
+        if buf.is_empty() {
+            return Ok((0, Decoded::Nothing));
+        }
+
+        match &mut self.state {
+            State::Signature { read } => {
+                let take = (8 - *read).min(buf.len());
+                if buf[..take] != PNG_SIGNATURE[*read..*read + take] {
+                    return Err(DecodeError::MissingSignature);
+                }
+
+                *read += take;
+                if *read == 8 {
+                    self.state = State::ChunkHeader {
+                        buf: [0; 8],
+                        read: 0,
+                    };
+                }
+
+                Ok((take, Decoded::Nothing))
+            }
+            State::ChunkHeader { buf: header, read } => {
+                let take = (8 - *read).min(buf.len());
+                header[*read..*read + take].copy_from_slice(&buf[..take]);
+                *read += take;
+
+                if *read < 8 {
+                    return Ok((take, Decoded::Nothing));
+                }
+
+                let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+                let ty: ChunkType = header[4..8].try_into()?;
+                self.order.check(ty)?;
+
+                let mut crc = Crc32::new();
+                crc.update(&header[4..8]);
+
+                self.state = State::ChunkData {
+                    ty,
+                    length,
+                    read: 0,
+                    data: Vec::new(),
+                    crc,
+                };
+
+                Ok((take, Decoded::ChunkBegin(length, ty)))
+            }
+            State::ChunkData {
+                ty,
+                length,
+                read,
+                data,
+                crc,
+            } => {
+                let remaining = (*length - *read) as usize;
+                let take = remaining.min(buf.len());
+                let ty = *ty;
+
+                crc.update(&buf[..take]);
+
+                if ty == ChunkType::IDAT {
+                    *read += take as u32;
+                    if *read == *length {
+                        let computed = crc.finalize();
+                        self.state = State::ChunkCrc {
+                            ty,
+                            computed,
+                            buf: [0; 4],
+                            read: 0,
+                        };
+                    }
+                    return Ok((take, Decoded::ImageData));
+                }
+
+                check_allocation(&mut self.bytes_allocated, &self.limits, take)?;
+                data.extend_from_slice(&buf[..take]);
+                *read += take as u32;
+
+                if *read < *length {
+                    return Ok((take, Decoded::Nothing));
+                }
+
+                let computed = crc.finalize();
+                let data = std::mem::take(data);
+                self.state = State::ChunkCrc {
+                    ty,
+                    computed,
+                    buf: [0; 4],
+                    read: 0,
+                };
+
+                if ty == ChunkType::IHDR {
+                    let (width, height, bit_depth, color_type, interlace_method) =
+                        parse_ihdr(&data)?;
+                    return Ok((
+                        take,
+                        Decoded::Header {
+                            width,
+                            height,
+                            bit_depth,
+                            color_type,
+                            interlace_method,
+                        },
+                    ));
+                }
+
+                Ok((take, Decoded::Nothing))
+            }
+            State::ChunkCrc {
+                ty,
+                computed,
+                buf: crc,
+                read,
+            } => {
+                let take = (4 - *read).min(buf.len());
+                crc[*read..*read + take].copy_from_slice(&buf[..take]);
+                *read += take;
+
+                if *read < 4 {
+                    return Ok((take, Decoded::Nothing));
+                }
+
+                let ty = *ty;
+                let computed = *computed;
+                let expected = u32::from_be_bytes(*crc);
+
+                if computed != expected {
+                    return Err(DecodeError::CrcMismatch {
+                        ty,
+                        expected,
+                        computed,
+                    });
+                }
+
+                self.state = State::ChunkHeader {
+                    buf: [0; 8],
+                    read: 0,
+                };
+
+                if ty == ChunkType::IEND {
+                    return Ok((take, Decoded::ImageEnd));
+                }
+
+                Ok((take, Decoded::ChunkComplete(ty)))
+            }
+        }
+    }
+}
+
+fn parse_ihdr(
+    data: &[u8],
+) -> Result<(u32, u32, BitDepth, ColorType, InterlaceMethod), DecodeError> {
+    if data.len() != 13 {
+        return Err(DecodeError::InvalidIHDRLength);
+    }
+
+    let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+    let bit_depth: BitDepth = data[8].try_into()?;
+    let color_type: ColorType = data[9].try_into()?;
+    let interlace_method: InterlaceMethod = data[12].try_into()?;
+
+    Ok((width, height, bit_depth, color_type, interlace_method))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc::crc32;
+
+    fn ihdr_chunk(crc_byte_to_corrupt: Option<usize>) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u32.to_be_bytes()); // width
+        data.extend_from_slice(&1u32.to_be_bytes()); // height
+        data.push(8); // bit depth
+        data.push(0); // color type: grayscale
+        data.push(0); // compression method
+        data.push(0); // filter method
+        data.push(0); // interlace method: none
+
+        let mut crc_input = b"IHDR".to_vec();
+        crc_input.extend_from_slice(&data);
+        let mut crc = crc32(&crc_input).to_be_bytes();
+        if let Some(i) = crc_byte_to_corrupt {
+            crc[i] ^= 0xFF;
+        }
+
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(b"IHDR");
+        chunk.extend_from_slice(&data);
+        chunk.extend_from_slice(&crc);
+        chunk
+    }
+
+    /// Feeds `buf` into `decoder` a piece at a time (as `update` only
+    /// consumes as much as the current state needs per call), returning the
+    /// last `Decoded` event produced.
+    fn feed(decoder: &mut StreamingDecoder, mut buf: &[u8]) -> Result<Decoded, DecodeError> {
+        let mut last = Decoded::Nothing;
+        while !buf.is_empty() {
+            let (consumed, decoded) = decoder.update(buf)?;
+            last = decoded;
+            buf = &buf[consumed..];
+        }
+        Ok(last)
+    }
+
+    #[test]
+    fn accepts_a_chunk_with_a_correct_crc() {
+        let mut decoder = StreamingDecoder::new();
+        feed(&mut decoder, PNG_SIGNATURE).unwrap();
+
+        let decoded = feed(&mut decoder, &ihdr_chunk(None)).unwrap();
+        assert!(matches!(decoded, Decoded::ChunkComplete(ChunkType::IHDR)));
+    }
+
+    #[test]
+    fn rejects_a_chunk_with_a_corrupted_crc() {
+        let mut decoder = StreamingDecoder::new();
+        feed(&mut decoder, PNG_SIGNATURE).unwrap();
+
+        assert!(matches!(
+            feed(&mut decoder, &ihdr_chunk(Some(0))),
+            Err(DecodeError::CrcMismatch {
+                ty: ChunkType::IHDR,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_non_idat_chunk_data_past_the_allocation_limit() {
+        let mut decoder = StreamingDecoder::with_limits(Limits {
+            max_total_bytes: 4,
+            ..Limits::default()
+        });
+        feed(&mut decoder, PNG_SIGNATURE).unwrap();
+
+        assert!(matches!(
+            feed(&mut decoder, &ihdr_chunk(None)),
+            Err(DecodeError::LimitExceeded)
+        ));
+    }
+}